@@ -0,0 +1,70 @@
+//! Lexer benchmarks. Run with `cargo bench`.
+
+use rave::lexer::{
+    tokens::{Ident, Number, Plus, PlusEqual, Semicolon, Star},
+    Lexer, TokenBox, TokenKind,
+};
+use std::{hint::black_box, time::Instant};
+
+/// Builds roughly `size` bytes of `ident += 123 * ident;` statements.
+fn synthetic_source(size: usize) -> String {
+    let mut source = String::with_capacity(size + 32);
+    let mut i = 0u32;
+    while source.len() < size {
+        source.push_str(&format!("abc += {i} * xyz + 0x{i:x};\n"));
+        i += 1;
+    }
+    source
+}
+
+fn bench(name: &str, iterations: u32, mut f: impl FnMut()) {
+    f();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        f();
+    }
+    let elapsed = start.elapsed() / iterations;
+    println!("{name:<24} {elapsed:?}/iter");
+}
+
+fn lex_on_demand(source: &str) -> usize {
+    let mut lexer = Lexer::<2>::new(source);
+    let mut count = 0;
+    loop {
+        if lexer.get::<Ident>().is_some()
+            || lexer.get::<PlusEqual>().is_some()
+            || lexer.get::<Plus>().is_some()
+            || lexer.get::<Star>().is_some()
+            || lexer.get::<Number>().is_some()
+            || lexer.get::<Semicolon>().is_some()
+        {
+            count += 1;
+        } else {
+            return count;
+        }
+    }
+}
+
+fn main() {
+    let source = synthetic_source(1 << 20);
+    let registry = [
+        TokenKind::of::<Ident>(),
+        TokenKind::of::<Number>(),
+        TokenKind::of::<Plus>(),
+        TokenKind::of::<PlusEqual>(),
+        TokenKind::of::<Star>(),
+        TokenKind::of::<Semicolon>(),
+    ];
+
+    bench("lex 1MB on demand", 10, || {
+        black_box(lex_on_demand(black_box(&source)));
+    });
+    bench("lex 1MB tokenize_all", 10, || {
+        let lexer = Lexer::<1>::new(black_box(&source));
+        black_box(lexer.tokenize_all(&registry).unwrap());
+    });
+    bench("TokenBox round trip", 1_000_000, || {
+        let token = TokenBox::new(black_box(0usize..1usize));
+        black_box(unsafe { token.downcast::<core::ops::Range<usize>>() });
+    });
+}
@@ -1,9 +1,110 @@
+use crate::span::Span;
+use core::fmt;
+
 #[derive(Debug)]
 pub enum Error<'a> {
     UnexpectedToken {
         unexpected: &'a str,
         expected: &'a str,
     },
+    /// A fixed-capacity buffer ran out of space.
+    CapacityExceeded { capacity: usize },
+    /// A token was peeked before the ones in front of it.
+    LookaheadSkipped { requested: usize, peeked: usize },
+    /// More tokens were peeked than the lexer can buffer.
+    LookaheadExceeded { needed: usize, capacity: usize },
+    /// A malformed literal.
+    InvalidLiteral { span: Span, expected: &'static str },
+    /// A token whose closing delimiter never came. `span` covers the opening
+    /// delimiter.
+    Unterminated { span: Span, expected: &'a str },
+    /// A closing delimiter with no opening one before it.
+    UnmatchedDelimiter { span: Span, found: &'a str },
+    /// Syntax from other languages that has no equivalent here.
+    Unsupported { found: &'a str, help: &'static str },
+    /// A token type doesn't fit in a [`TokenBox`](crate::lexer::TokenBox).
+    TokenTooLarge { size: usize, align: usize },
+}
+impl Error<'_> {
+    /// Whether this error comes from calling the lexer wrong, rather than
+    /// from the input.
+    pub fn is_misuse(&self) -> bool {
+        matches!(
+            self,
+            Self::LookaheadSkipped { .. }
+                | Self::LookaheadExceeded { .. }
+                | Self::TokenTooLarge { .. }
+        )
+    }
 }
+impl fmt::Display for Error<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedToken {
+                unexpected,
+                expected,
+            } => write!(f, "unexpected `{unexpected}`, expected {expected}"),
+            Self::CapacityExceeded { capacity } => {
+                write!(f, "more than {capacity} tokens")
+            }
+            Self::LookaheadSkipped { requested, peeked } => write!(
+                f,
+                "peeked token {requested} with only {peeked} tokens peeked before it"
+            ),
+            Self::LookaheadExceeded { needed, capacity } => write!(
+                f,
+                "needed {needed} tokens of lookahead, but only {capacity} can be buffered"
+            ),
+            Self::InvalidLiteral { span, expected } => {
+                write!(f, "invalid literal at {span}, expected {expected}")
+            }
+            Self::Unterminated { span, expected } => {
+                write!(f, "unterminated token at {span}, expected {expected}")
+            }
+            Self::UnmatchedDelimiter { span, found } => {
+                write!(f, "unmatched `{found}` at {span}")
+            }
+            Self::Unsupported { found, help } => write!(f, "rave has no `{found}`; {help}"),
+            Self::TokenTooLarge { size, align } => write!(
+                f,
+                "token of size {size} and align {align} doesn't fit in 16 bytes"
+            ),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for Error<'_> {}
 
 pub type Result<'a, T> = core::result::Result<T, Error<'a>>;
+
+/// A numeric literal that lexed fine but can't be evaluated as the requested
+/// type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LiteralError {
+    /// The value doesn't fit in `ty`.
+    OutOfRange { span: Span, ty: &'static str },
+    /// A negated literal was evaluated as the unsigned type `ty`.
+    Negative { span: Span, ty: &'static str },
+    /// A literal with a fraction was evaluated as an integer.
+    NotAnInteger { span: Span },
+}
+impl LiteralError {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::OutOfRange { span, .. }
+            | Self::Negative { span, .. }
+            | Self::NotAnInteger { span } => *span,
+        }
+    }
+}
+impl fmt::Display for LiteralError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OutOfRange { ty, .. } => write!(f, "literal out of range for {ty}"),
+            Self::Negative { ty, .. } => write!(f, "cannot negate a literal of type {ty}"),
+            Self::NotAnInteger { .. } => write!(f, "expected an integer literal"),
+        }
+    }
+}
+#[cfg(feature = "std")]
+impl std::error::Error for LiteralError {}
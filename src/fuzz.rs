@@ -0,0 +1,99 @@
+//! Entry points for fuzz targets. None of these should ever panic, whatever
+//! the input.
+
+use crate::error::Result;
+use crate::lexer::{tokens::*, Lexer, TokenKind};
+
+/// Every token type in [`tokens`](crate::lexer::tokens) that isn't behind a
+/// feature.
+fn registry() -> [TokenKind; 61] {
+    [
+        TokenKind::of::<Plus>(),
+        TokenKind::of::<Minus>(),
+        TokenKind::of::<Star>(),
+        TokenKind::of::<Slash>(),
+        TokenKind::of::<Percent>(),
+        TokenKind::of::<PlusEqual>(),
+        TokenKind::of::<MinusEqual>(),
+        TokenKind::of::<StarEqual>(),
+        TokenKind::of::<SlashEqual>(),
+        TokenKind::of::<PercentEqual>(),
+        TokenKind::of::<EqualEqual>(),
+        TokenKind::of::<BangEqual>(),
+        TokenKind::of::<LessEqual>(),
+        TokenKind::of::<GreaterEqual>(),
+        TokenKind::of::<AndAnd>(),
+        TokenKind::of::<OrOr>(),
+        TokenKind::of::<And>(),
+        TokenKind::of::<Or>(),
+        TokenKind::of::<Caret>(),
+        TokenKind::of::<Tilde>(),
+        TokenKind::of::<LessLess>(),
+        TokenKind::of::<GreaterGreater>(),
+        TokenKind::of::<AndEqual>(),
+        TokenKind::of::<OrEqual>(),
+        TokenKind::of::<CaretEqual>(),
+        TokenKind::of::<LessLessEqual>(),
+        TokenKind::of::<GreaterGreaterEqual>(),
+        TokenKind::of::<Bang>(),
+        TokenKind::of::<Equal>(),
+        TokenKind::of::<ColonEqual>(),
+        TokenKind::of::<Less>(),
+        TokenKind::of::<Greater>(),
+        TokenKind::of::<Semicolon>(),
+        TokenKind::of::<Comma>(),
+        TokenKind::of::<Dot>(),
+        TokenKind::of::<DotDot>(),
+        TokenKind::of::<DotDotEqual>(),
+        TokenKind::of::<Colon>(),
+        TokenKind::of::<Arrow>(),
+        TokenKind::of::<FatArrow>(),
+        TokenKind::of::<Question>(),
+        TokenKind::of::<QuestionDot>(),
+        TokenKind::of::<QuestionQuestion>(),
+        TokenKind::of::<LessThanOrEqualTo>(),
+        TokenKind::of::<GreaterThanOrEqualTo>(),
+        TokenKind::of::<NotEqualTo>(),
+        TokenKind::of::<RightwardsArrow>(),
+        TokenKind::of::<MiddleDot>(),
+        TokenKind::of::<LeftParen>(),
+        TokenKind::of::<RightParen>(),
+        TokenKind::of::<LeftBrace>(),
+        TokenKind::of::<RightBrace>(),
+        TokenKind::of::<LeftBracket>(),
+        TokenKind::of::<RightBracket>(),
+        TokenKind::of::<Ident>(),
+        TokenKind::of::<Label>(),
+        TokenKind::of::<Number>(),
+        TokenKind::of::<DocComment>(),
+        TokenKind::of::<Operator>(),
+        TokenKind::of::<Heredoc>(),
+        TokenKind::of::<ByteString>(),
+    ]
+}
+
+/// Lexes `input` with every built-in token, returning the number of tokens.
+///
+/// Input that isn't valid UTF-8 is lexed up to the first invalid byte.
+pub fn lex(input: &[u8]) -> Result<'_, usize> {
+    let input = match core::str::from_utf8(input) {
+        Ok(input) => input,
+        // SAFETY: `valid_up_to` is the length of the longest valid prefix
+        Err(err) => unsafe { core::str::from_utf8_unchecked(&input[..err.valid_up_to()]) },
+    };
+    let registry = registry();
+    let buffer = Lexer::<1>::new(input).tokenize_all(&registry)?;
+    Ok(buffer.len())
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn lex() {
+        assert_eq!(super::lex(b"a += 0x1f;").unwrap(), 4);
+        assert_eq!(super::lex(b"(b)\xff+").unwrap(), 3);
+        for input in [&b""[..], b"   ", b"0x", b"0b", b"1..2", b"\xe2\x89", b"#"] {
+            let _ = super::lex(input);
+        }
+    }
+}
@@ -1,20 +1,74 @@
+pub mod array;
+#[cfg(feature = "alloc")]
+pub mod buffer;
+pub mod lexed;
+#[cfg(feature = "alloc")]
+pub mod record;
 pub mod tokens;
+#[cfg(feature = "alloc")]
+pub mod tree;
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::span::Span;
 use core::ops::Index;
-use core::{any::TypeId, mem::MaybeUninit, ops::Range};
+use core::{any::TypeId, mem::MaybeUninit};
 
 pub trait Token: 'static {
     // dyn-able
-    fn span(&self) -> &Range<usize>;
+    fn span(&self) -> &Span;
     // not dyn-able
-    fn parse(start: usize, input: &str) -> Option<Result<(Self, usize)>>
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
     where
         Self: Sized;
 }
 
+type ParseFn<T> = for<'i> fn(usize, &'i str) -> Option<Result<'i, T>>;
+
+/// A type-erased token type, used to lex without knowing token types
+/// statically.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenKind {
+    type_id: TypeId,
+    parse: ParseFn<usize>,
+    parse_boxed: ParseFn<(TokenBox, usize)>,
+}
+impl TokenKind {
+    pub fn of<T: Token>() -> Self {
+        Self {
+            type_id: TypeId::of::<T>(),
+            parse: |start, input| T::parse(start, input).map(|res| res.map(|(_, len)| len)),
+            parse_boxed: |start, input| {
+                T::parse(start, input).map(|res| {
+                    let (token, len) = res?;
+                    let token = TokenBox::try_new(token).map_err(|_| Error::TokenTooLarge {
+                        size: core::mem::size_of::<T>(),
+                        align: core::mem::align_of::<T>(),
+                    })?;
+                    Ok((token, len))
+                })
+            },
+        }
+    }
+    pub fn is<T: Token>(&self) -> bool {
+        self.type_id == TypeId::of::<T>()
+    }
+    /// Returns the number of bytes consumed.
+    pub fn parse<'i>(&self, start: usize, input: &'i str) -> Option<Result<'i, usize>> {
+        (self.parse)(start, input)
+    }
+    /// Like [`parse`](TokenKind::parse), but also returns the token.
+    pub fn parse_boxed<'i>(
+        &self,
+        start: usize,
+        input: &'i str,
+    ) -> Option<Result<'i, (TokenBox, usize)>> {
+        (self.parse_boxed)(start, input)
+    }
+}
+
 #[repr(align(16))]
 #[derive(Debug)]
+#[allow(dead_code)]
 pub struct Aligned16Bytes([MaybeUninit<u8>; 16]);
 
 #[derive(Debug)]
@@ -50,98 +104,400 @@ impl TokenBox {
     /// ## Panics
     /// Panics if size or align of `T` > 16
     pub fn new<T: 'static>(value: T) -> Self {
-        assert!(core::mem::size_of::<T>() <= 16);
-        assert!(core::mem::align_of::<T>() <= 16);
+        match Self::try_new(value) {
+            Ok(token) => token,
+            Err(_) => panic!("size or align of token > 16"),
+        }
+    }
+    /// Gives `value` back if its size or align is > 16.
+    pub fn try_new<T: 'static>(value: T) -> core::result::Result<Self, T> {
+        if core::mem::size_of::<T>() > 16 || core::mem::align_of::<T>() > 16 {
+            return Err(value);
+        }
         let mut array = Aligned16Bytes([MaybeUninit::uninit(); 16]);
         let src = &value as *const _ as *const u8;
         let dst = &mut array as *mut _ as *mut u8;
         unsafe {
             core::ptr::copy_nonoverlapping(src, dst, core::mem::size_of::<T>());
         }
-        Self {
+        Ok(Self {
             data: array,
             type_id: TypeId::of::<T>(),
-        }
+        })
     }
 }
 
+#[derive(Debug)]
+struct Slot {
+    token: TokenBox,
+    start: usize,
+    end: usize,
+}
+
+/// A parse attempt that did not end up in the lookahead buffer.
+#[derive(Debug)]
+struct Attempt {
+    start: usize,
+    type_id: TypeId,
+    slot: Option<Slot>,
+}
+
+/// Lexes tokens on demand, with up to `LOOKAHEAD` tokens of lookahead.
+///
+/// Peeked tokens are kept in a FIFO buffer. `get` consumes from the front of
+/// it: if the front token is a `T` it is returned as is, and if it is a
+/// different type, `T` is tried at the same position and replaces the whole
+/// buffer on success. Only once the buffer is empty does `get` lex past it.
 pub struct Lexer<'a, const LOOKAHEAD: usize> {
     input: &'a str,
     index: usize,
-    buf: heapless::Deque<TokenBox, LOOKAHEAD>,
+    buf: heapless::Deque<Slot, LOOKAHEAD>,
+    /// The last failed or displaced parse, so that probing several token
+    /// types at the same position doesn't lex the same input twice.
+    attempt: Option<Attempt>,
+    /// Where the last consumed token ended.
+    consumed: usize,
+    keywords: &'a [&'a str],
 }
 impl<'a, const LOOKAHEAD: usize> Lexer<'a, LOOKAHEAD> {
     pub fn new(input: &'a str) -> Self {
-        Self {
+        let mut lexer = Self {
             input,
             index: 0,
             buf: heapless::Deque::new(),
+            attempt: None,
+            consumed: 0,
+            keywords: &[],
+        };
+        lexer.index = lexer.skip_whitespace(0);
+        lexer
+    }
+    /// Creates a lexer with a reserved-word set, so one binary can lex
+    /// dialects with different keywords.
+    ///
+    /// Keywords still lex as [`Ident`](tokens::Ident) through `get`, since
+    /// tokens don't see the lexer's state. Use [`get_ident`](Lexer::get_ident)
+    /// and [`get_keyword`](Lexer::get_keyword) to tell them apart.
+    pub fn with_keywords(input: &'a str, keywords: &'a [&'a str]) -> Self {
+        Self {
+            keywords,
+            ..Self::new(input)
+        }
+    }
+    pub fn keywords(&self) -> &'a [&'a str] {
+        self.keywords
+    }
+    pub fn is_keyword(&self, text: &str) -> bool {
+        self.keywords.contains(&text)
+    }
+    /// Consumes the next token if it is an identifier that isn't one of the
+    /// [`keywords`](Lexer::keywords).
+    pub fn get_ident(&mut self) -> Option<tokens::Ident> {
+        let (input, keywords) = (self.input, self.keywords);
+        match self.peek::<tokens::Ident>()? {
+            Ok(ident) if !keywords.contains(&&input[*ident.span()]) => self.get_buffered(),
+            _ => None,
+        }
+    }
+    /// Consumes the next token if it is `keyword`, which has to be one of the
+    /// [`keywords`](Lexer::keywords).
+    pub fn get_keyword(&mut self, keyword: &str) -> Option<tokens::Ident> {
+        if !self.is_keyword(keyword) {
+            return None;
+        }
+        let input = self.input;
+        match self.peek::<tokens::Ident>()? {
+            Ok(ident) if &input[*ident.span()] == keyword => self.get_buffered(),
+            _ => None,
         }
     }
     /// ## Panics
     /// Panics if size or align of `T` > 16
-    pub fn peek<T: Token + 'static>(&mut self) -> Option<Result<&T>> {
-        if !self.buf.is_empty() && self.buf.back().unwrap().is::<T>() {
-            let val = self.buf.back().unwrap();
-            let downcasted = unsafe { val.downcast_ref::<T>() };
-            return Some(Ok(downcasted));
-        }
-        let token = match T::parse(self.index, &self.input[self.index..]) {
-            Some(Ok(token)) => {
-                self.index = token.1;
-                self.trim();
-                TokenBox::new(token.0)
-            }
-            Some(Err(err)) => return Some(Err(err)),
-            None => return None,
-        };
-        self.buf.push_back(token).expect("Out of space");
-        Some(Ok(unsafe { self.buf.back().unwrap().downcast_ref() }))
+    pub fn peek<T: Token + 'static>(&mut self) -> Option<Result<'_, &T>> {
+        self.peek_n(0)
+    }
+    /// If a token of a different type is already buffered at position `n`,
+    /// `T` is tried in its place. On success, the old token and everything
+    /// after it is discarded.
+    ///
+    /// Returns [`Error::LookaheadSkipped`] if `n - 1` has not been previously
+    /// peeked. Use [`peek_n_with`](Lexer::peek_n_with) to fill the tokens in
+    /// front automatically.
+    ///
+    /// Returns [`Error::LookaheadExceeded`] if `n` >= `LOOKAHEAD`.
+    ///
+    /// ## Panics
+    /// Panics if size or align of `T` > 16
+    pub fn peek_n<T: Token + 'static>(&mut self, n: usize) -> Option<Result<'_, &T>> {
+        match self.try_peek_n(n) {
+            Some(Err(err @ Error::TokenTooLarge { .. })) => panic!("{err}"),
+            res => res,
+        }
+    }
+    /// Like [`peek_n`](Lexer::peek_n), but first lexes any tokens in front of
+    /// `n` that haven't been peeked yet with `registry`. The longest match
+    /// wins, with ties going to whichever kind comes first in `registry`.
+    ///
+    /// Returns `None` if the input ends before position `n`.
+    ///
+    /// ## Panics
+    /// Panics if size or align of `T` or any filled token > 16
+    pub fn peek_n_with<T: Token + 'static>(
+        &mut self,
+        n: usize,
+        registry: &[TokenKind],
+    ) -> Option<Result<'_, &T>> {
+        match self.fill(n, registry)? {
+            Err(err @ Error::TokenTooLarge { .. }) => panic!("{err}"),
+            Err(err) => return Some(Err(err)),
+            Ok(()) => {}
+        }
+        self.peek_n(n)
     }
     /// ## Panics
-    /// Panics if:
-    /// - `n - 1` has not been previously peeked.
-    /// - size or align of `T` > 16
-    pub fn peek_n<T: Token + 'static>(&mut self, n: usize) -> Option<Result<&T>> {
-        if self.buf.len() > n && self.buf.iter().nth(n).unwrap().is::<T>() {
-            let val = self.buf.iter().nth(n).unwrap();
-            let downcasted = unsafe { val.downcast_ref::<T>() };
-            return Some(Ok(downcasted));
-        }
-        assert_eq!(self.buf.len(), n);
-        let token = match T::parse(self.index, &self.input[self.index..]) {
-            Some(Ok(token)) => {
-                self.index = token.1;
-                self.trim();
-                TokenBox::new(token.0)
+    /// Panics if size or align of `T` > 16
+    pub fn get<T: Token + 'static>(&mut self) -> Option<Result<'_, T>> {
+        match self.try_get() {
+            Some(Err(err)) if err.is_misuse() => panic!("{err}"),
+            res => res,
+        }
+    }
+    /// Like [`peek`](Lexer::peek), but never panics.
+    pub fn try_peek<T: Token + 'static>(&mut self) -> Option<Result<'_, &T>> {
+        self.try_peek_n(0)
+    }
+    /// Like [`peek_n`](Lexer::peek_n), but never panics. Instead it returns
+    /// [`Error::LookaheadSkipped`], [`Error::LookaheadExceeded`] or
+    /// [`Error::TokenTooLarge`].
+    pub fn try_peek_n<T: Token + 'static>(&mut self, n: usize) -> Option<Result<'_, &T>> {
+        match self.buf.iter().nth(n) {
+            Some(slot) if slot.token.is::<T>() => {}
+            Some(slot) => {
+                let slot = match self.parse_at::<T>(slot.start)? {
+                    Ok(slot) => slot,
+                    Err(err) => return Some(Err(err)),
+                };
+                self.truncate(n);
+                self.index = slot.end;
+                // `truncate` made room
+                let _ = self.buf.push_back(slot);
+            }
+            None => {
+                if self.buf.len() != n {
+                    return Some(Err(Error::LookaheadSkipped {
+                        requested: n,
+                        peeked: self.buf.len(),
+                    }));
+                }
+                if n >= LOOKAHEAD {
+                    return Some(Err(Error::LookaheadExceeded {
+                        needed: n + 1,
+                        capacity: LOOKAHEAD,
+                    }));
+                }
+                let slot = match self.parse_at::<T>(self.index)? {
+                    Ok(slot) => slot,
+                    Err(err) => return Some(Err(err)),
+                };
+                self.index = slot.end;
+                let _ = self.buf.push_back(slot);
             }
-            Some(Err(err)) => return Some(Err(err)),
-            None => return None,
+        }
+        let slot = self.buf.iter().nth(n)?;
+        Some(Ok(unsafe { slot.token.downcast_ref() }))
+    }
+    /// Like [`peek_n_with`](Lexer::peek_n_with), but never panics.
+    pub fn try_peek_n_with<T: Token + 'static>(
+        &mut self,
+        n: usize,
+        registry: &[TokenKind],
+    ) -> Option<Result<'_, &T>> {
+        if let Err(err) = self.fill(n, registry)? {
+            return Some(Err(err));
+        }
+        self.try_peek_n(n)
+    }
+    /// Like [`get`](Lexer::get), but never panics. Instead it returns
+    /// [`Error::TokenTooLarge`].
+    pub fn try_get<T: Token + 'static>(&mut self) -> Option<Result<'_, T>> {
+        if let Some(token) = self.get_buffered() {
+            return Some(Ok(token));
+        }
+        let start = self.buf.front().map_or(self.index, |slot| slot.start);
+        let slot = match self.parse_at::<T>(start)? {
+            Ok(slot) => slot,
+            Err(err) => return Some(Err(err)),
         };
-        self.buf.push_back(token).expect("Out of space");
-        Some(Ok(unsafe { self.buf.back().unwrap().downcast_ref() }))
-    }
-    pub fn get<T: Token + 'static>(&mut self) -> Option<Result<T>> {
-        if !self.buf.is_empty() && self.buf.iter().last().unwrap().is::<T>() {
-            let val = self.buf.pop_front().unwrap();
-            let downcasted = unsafe { val.downcast::<T>() };
-            return Some(Ok(downcasted));
-        }
-        T::parse(self.index, &self.input[self.index..]).map(|res| {
-            res.map(|val| {
-                self.index += val.1;
-                self.trim();
-                val.0
-            })
-        })
+        self.truncate(0);
+        self.index = slot.end;
+        let token: T = unsafe { slot.token.downcast() };
+        self.consumed = token.span().end;
+        Some(Ok(token))
+    }
+    /// Consumes the front of the lookahead buffer if it is a `T`, without
+    /// lexing anything.
+    pub fn get_buffered<T: Token + 'static>(&mut self) -> Option<T> {
+        if !self.buf.front()?.token.is::<T>() {
+            return None;
+        }
+        let slot = self.buf.pop_front()?;
+        let token: T = unsafe { slot.token.downcast() };
+        self.consumed = token.span().end;
+        Some(token)
+    }
+    /// The number of peeked tokens that haven't been consumed yet.
+    pub fn buffered(&self) -> usize {
+        self.buf.len()
+    }
+    /// Where the next unconsumed token starts, after any whitespace.
+    pub fn current_offset(&self) -> usize {
+        self.registry_start()
+    }
+    /// The 1-based line and column of [`current_offset`](Lexer::current_offset).
+    /// Columns count chars, not bytes.
+    pub fn current_line_col(&self) -> (usize, usize) {
+        let before = &self.input[..self.current_offset()];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let line = before.matches('\n').count() + 1;
+        (line, before[line_start..].chars().count() + 1)
+    }
+    /// The span from `checkpoint`, an earlier
+    /// [`current_offset`](Lexer::current_offset), to the end of the last
+    /// consumed token.
+    pub fn span_since(&self, checkpoint: usize) -> Span {
+        Span::new(checkpoint, self.consumed.max(checkpoint))
+    }
+    /// Parses a `T` at `start`, going through the attempt cache.
+    fn parse_at<T: Token + 'static>(&mut self, start: usize) -> Option<Result<'a, Slot>> {
+        let type_id = TypeId::of::<T>();
+        if let Some(attempt) = &self.attempt {
+            if attempt.start == start && attempt.type_id == type_id {
+                return match self.attempt.take().unwrap().slot {
+                    Some(slot) => Some(Ok(slot)),
+                    None => {
+                        self.attempt = Some(Attempt {
+                            start,
+                            type_id,
+                            slot: None,
+                        });
+                        None
+                    }
+                };
+            }
+        }
+        let input = self.input;
+        match T::parse(start, &input[start..]) {
+            Some(Ok((token, consumed))) => {
+                let Ok(token) = TokenBox::try_new(token) else {
+                    return Some(Err(Error::TokenTooLarge {
+                        size: core::mem::size_of::<T>(),
+                        align: core::mem::align_of::<T>(),
+                    }));
+                };
+                Some(Ok(Slot {
+                    token,
+                    start,
+                    end: self.skip_whitespace(start + consumed),
+                }))
+            }
+            Some(Err(err)) => Some(Err(err)),
+            None => {
+                self.attempt = Some(Attempt {
+                    start,
+                    type_id,
+                    slot: None,
+                });
+                None
+            }
+        }
+    }
+    /// Drops every buffered token from position `n` onwards, keeping the
+    /// one at `n` as the cached attempt.
+    fn truncate(&mut self, n: usize) {
+        while self.buf.len() > n {
+            let slot = self.buf.pop_back().unwrap();
+            if self.buf.len() == n {
+                self.attempt = Some(Attempt {
+                    start: slot.start,
+                    type_id: slot.token.type_id,
+                    slot: Some(slot),
+                });
+            }
+        }
+    }
+    /// Lexes tokens with `registry` until `n` are buffered.
+    fn fill(&mut self, n: usize, registry: &[TokenKind]) -> Option<Result<'a, ()>> {
+        while self.buf.len() < n {
+            if self.buf.len() >= LOOKAHEAD {
+                return Some(Err(Error::LookaheadExceeded {
+                    needed: n + 1,
+                    capacity: LOOKAHEAD,
+                }));
+            }
+            let start = self.skip_whitespace(self.index);
+            if start == self.input.len() {
+                return None;
+            }
+            let (_, token, len) = match self.parse_kind(registry, start, TokenKind::parse_boxed) {
+                Ok(token) => token,
+                Err(err) => return Some(Err(err)),
+            };
+            self.index = self.skip_whitespace(start + len);
+            let _ = self.buf.push_back(Slot {
+                token,
+                start,
+                end: self.index,
+            });
+        }
+        Some(Ok(()))
+    }
+    /// Where lexing the rest of the input with a registry starts: the first
+    /// buffered token, or the current position if nothing is buffered.
+    fn registry_start(&self) -> usize {
+        let index = self.buf.front().map_or(self.index, |slot| slot.start);
+        self.skip_whitespace(index)
     }
-    fn trim(&mut self) {
-        for (i, c) in self.input[self.index..].char_indices() {
-            if !c.is_whitespace() {
-                self.index += i;
-                return;
+    /// Lexes a single token at `index` with `registry`, parsing each kind
+    /// with `parse`. Returns the winner's index in the registry, what `parse`
+    /// returned for it, and its length.
+    ///
+    /// The longest match wins, with ties going to whichever kind comes first
+    /// in `registry`. Misuse errors are returned as soon as any kind hits
+    /// them.
+    fn parse_kind<T>(
+        &self,
+        registry: &[TokenKind],
+        index: usize,
+        parse: impl Fn(&TokenKind, usize, &'a str) -> Option<Result<'a, (T, usize)>>,
+    ) -> Result<'a, (u16, T, usize)> {
+        let input = self.input;
+        let mut best: Option<(u16, T, usize)> = None;
+        let mut error = None;
+        for (i, kind) in registry.iter().enumerate() {
+            match parse(kind, index, &input[index..]) {
+                Some(Ok((token, len))) if len > best.as_ref().map_or(0, |(_, _, len)| *len) => {
+                    best = Some((i as u16, token, len));
+                }
+                Some(Err(err)) if err.is_misuse() => return Err(err),
+                Some(Err(err)) if error.is_none() => error = Some(err),
+                _ => {}
             }
         }
+        best.ok_or_else(|| {
+            error.unwrap_or_else(|| {
+                let len = input[index..].chars().next().map_or(0, char::len_utf8);
+                Error::UnexpectedToken {
+                    unexpected: &input[index..index + len],
+                    expected: "token",
+                }
+            })
+        })
+    }
+    fn skip_whitespace(&self, from: usize) -> usize {
+        self.input[from..]
+            .char_indices()
+            .find(|(_, c)| !c.is_whitespace())
+            .map_or(self.input.len(), |(i, _)| from + i)
     }
 }
 impl<'a, const LOOKAHEAD: usize, T> Index<T> for Lexer<'a, LOOKAHEAD>
@@ -157,6 +513,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::{tokens::*, *};
+    use core::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn lexer() {
@@ -170,4 +527,538 @@ mod tests {
         assert_eq!(b.eval(&lexer), "b");
         assert_eq!(c.eval::<1, u32>(&lexer), 0x100);
     }
+
+    static COUNTED_PARSES: AtomicUsize = AtomicUsize::new(0);
+
+    struct Counted(Span);
+    impl Token for Counted {
+        fn span(&self) -> &Span {
+            &self.0
+        }
+        fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+        where
+            Self: Sized,
+        {
+            COUNTED_PARSES.fetch_add(1, Ordering::Relaxed);
+            Ident::parse(start, input).map(|res| res.map(|(ident, len)| (Self(*ident.span()), len)))
+        }
+    }
+
+    #[test]
+    fn peek_cache() {
+        let mut lexer = Lexer::<2>::new("abc 1");
+        assert!(lexer.peek::<Number>().is_none());
+        assert!(lexer.peek::<Counted>().is_some());
+        assert!(lexer.peek::<Plus>().is_none());
+        assert!(lexer.peek::<Counted>().is_some());
+        assert!(lexer.peek::<Ident>().is_some());
+        assert!(lexer.peek::<Counted>().is_some());
+        assert_eq!(COUNTED_PARSES.load(Ordering::Relaxed), 1);
+
+        let counted = lexer.get::<Counted>().unwrap().unwrap();
+        assert_eq!(counted.span(), &(0..3));
+        let number = lexer.get::<Number>().unwrap().unwrap();
+        assert_eq!(number.eval::<2, u32>(&lexer), 1);
+    }
+
+    struct Big(Span, #[allow(dead_code)] [u64; 2]);
+    impl Token for Big {
+        fn span(&self) -> &Span {
+            &self.0
+        }
+        fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+        where
+            Self: Sized,
+        {
+            Ident::parse(start, input)
+                .map(|res| res.map(|(ident, len)| (Self(*ident.span(), [0; 2]), len)))
+        }
+    }
+
+    #[test]
+    fn try_peek() {
+        let mut lexer = Lexer::<1>::new("a b");
+        assert!(matches!(
+            lexer.try_peek_n::<Ident>(1),
+            Some(Err(Error::LookaheadSkipped {
+                requested: 1,
+                peeked: 0
+            }))
+        ));
+        assert!(matches!(
+            lexer.try_peek::<Big>(),
+            Some(Err(Error::TokenTooLarge { .. }))
+        ));
+        assert!(lexer.try_peek::<Ident>().unwrap().is_ok());
+        assert!(matches!(
+            lexer.try_peek_n::<Ident>(1),
+            Some(Err(Error::LookaheadExceeded {
+                needed: 2,
+                capacity: 1
+            }))
+        ));
+        assert!(TokenBox::try_new([0u64; 3]).is_err());
+        assert!(matches!(
+            lexer.peek_n::<Ident>(1),
+            Some(Err(Error::LookaheadExceeded { .. }))
+        ));
+    }
+
+    #[test]
+    fn range() {
+        let mut lexer = Lexer::<1>::new("0..10 a..=b");
+        assert!(lexer.get::<Number>().unwrap().is_ok());
+        assert!(lexer.peek::<Dot>().unwrap().is_ok());
+        assert!(lexer.peek::<DotDotEqual>().is_none());
+        assert!(lexer.get::<DotDot>().unwrap().is_ok());
+        let end = lexer.get::<Number>().unwrap().unwrap();
+        assert_eq!(end.eval::<1, u32>(&lexer), 10);
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<DotDot>().unwrap().is_ok());
+        assert!(lexer.get::<DotDotEqual>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn doc_comment() {
+        let mut lexer = Lexer::<1>::new("/// line\n/** block */ //// not /**/");
+        let line = lexer.get::<DocComment>().unwrap().unwrap();
+        assert_eq!(line.eval(&lexer), " line");
+        let block = lexer.get::<DocComment>().unwrap().unwrap();
+        assert_eq!(block.eval(&lexer), " block ");
+        assert!(lexer.get::<DocComment>().is_none());
+        assert!(matches!(
+            Lexer::<1>::new("/** open").get::<DocComment>(),
+            Some(Err(Error::Unterminated { span, .. })) if span == (0..3)
+        ));
+
+        let mut lexer = Lexer::<1>::new("/// doc\r\nfn");
+        let line = lexer.get::<DocComment>().unwrap().unwrap();
+        assert_eq!(line.span(), &(0..7));
+        assert_eq!(line.eval(&lexer), " doc");
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn peek_n_with() {
+        let registry = [
+            TokenKind::of::<Ident>(),
+            TokenKind::of::<Colon>(),
+            TokenKind::of::<ColonEqual>(),
+        ];
+        let mut lexer = Lexer::<4>::new("x := 1");
+        assert!(matches!(
+            lexer.peek_n::<Number>(2),
+            Some(Err(Error::LookaheadSkipped { .. }))
+        ));
+        let number = lexer.peek_n_with::<Number>(2, &registry).unwrap().unwrap();
+        assert_eq!(number.span(), &(5..6));
+        assert!(lexer.peek_n::<ColonEqual>(1).unwrap().is_ok());
+        assert!(lexer.peek_n_with::<Ident>(3, &registry).is_none());
+        assert!(matches!(
+            Lexer::<3>::new("x ; y").try_peek_n_with::<Ident>(2, &registry),
+            Some(Err(Error::UnexpectedToken { .. }))
+        ));
+        assert!(matches!(
+            Lexer::<1>::new("x y").try_peek_n_with::<Ident>(1, &registry),
+            Some(Err(Error::LookaheadExceeded { .. }))
+        ));
+    }
+
+    #[test]
+    fn get_is_fifo() {
+        let mut lexer = Lexer::<3>::new("a + b c");
+        assert!(lexer.peek_n::<Ident>(0).unwrap().is_ok());
+        assert!(lexer.peek_n::<Plus>(1).unwrap().is_ok());
+        assert!(lexer.peek_n::<Ident>(2).unwrap().is_ok());
+        assert_eq!(lexer.buffered(), 3);
+
+        assert!(lexer.get_buffered::<Plus>().is_none());
+        let a = lexer.get::<Ident>().unwrap().unwrap();
+        assert_eq!(a.eval(&lexer), "a");
+        assert!(lexer.get_buffered::<Plus>().is_some());
+        assert!(lexer.get::<Plus>().is_none());
+        let b = lexer.get::<Ident>().unwrap().unwrap();
+        assert_eq!(b.eval(&lexer), "b");
+        assert_eq!(lexer.buffered(), 0);
+        let c = lexer.get::<Ident>().unwrap().unwrap();
+        assert_eq!(c.eval(&lexer), "c");
+
+        let mut lexer = Lexer::<2>::new("+= 1");
+        assert!(lexer.peek::<Plus>().unwrap().is_ok());
+        let plus_equal = lexer.get::<PlusEqual>().unwrap().unwrap();
+        assert_eq!(plus_equal.span(), &(0..2));
+        assert_eq!(lexer.buffered(), 0);
+        assert!(lexer.get::<Number>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn eval_checked() {
+        use crate::error::LiteralError;
+
+        let mut lexer = Lexer::<1>::new("255 256 0b101 128 0o17 1.5");
+        let max = lexer.get::<Number>().unwrap().unwrap();
+        let over = lexer.get::<Number>().unwrap().unwrap();
+        let binary = lexer.get::<Number>().unwrap().unwrap();
+        let min = lexer.get::<Number>().unwrap().unwrap();
+        let octal = lexer.get::<Number>().unwrap().unwrap();
+        let float = lexer.get::<Number>().unwrap().unwrap();
+        assert_eq!(max.eval_checked::<1, u8>(&lexer), Ok(255));
+        assert_eq!(
+            over.eval_checked::<1, u8>(&lexer),
+            Err(LiteralError::OutOfRange {
+                span: (4..7).into(),
+                ty: "u8"
+            })
+        );
+        assert_eq!(binary.eval_checked::<1, i64>(&lexer), Ok(5));
+        assert_eq!(min.eval_negated_checked::<1, i8>(&lexer), Ok(-128));
+        assert!(matches!(
+            min.eval_checked::<1, i8>(&lexer),
+            Err(LiteralError::OutOfRange { .. })
+        ));
+        assert!(matches!(
+            binary.eval_negated_checked::<1, u32>(&lexer),
+            Err(LiteralError::Negative { .. })
+        ));
+        assert_eq!(octal.eval_checked::<1, u32>(&lexer), Ok(0o17));
+        assert!(matches!(
+            float.eval_checked::<1, u32>(&lexer),
+            Err(LiteralError::NotAnInteger { .. })
+        ));
+        assert_eq!(float.eval_float::<1, f64>(&lexer), 1.5);
+    }
+
+    #[test]
+    fn bitwise() {
+        let mut lexer = Lexer::<1>::new("a & b && c <<= d < e");
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.get::<And>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<And>().unwrap().is_ok());
+        assert!(lexer.get::<AndAnd>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<Less>().unwrap().is_ok());
+        assert!(lexer.peek::<LessLess>().unwrap().is_ok());
+        assert!(lexer.get::<LessLessEqual>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.get::<Less>().unwrap().is_ok());
+
+        let mut lexer = Lexer::<1>::new(">>");
+        assert!(lexer.get::<Greater>().unwrap().is_ok());
+        assert!(lexer.get::<Greater>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn arrows() {
+        let mut lexer = Lexer::<1>::new("-> => - 1");
+        assert!(lexer.peek::<Minus>().unwrap().is_ok());
+        assert!(lexer.get::<Arrow>().unwrap().is_ok());
+        assert!(lexer.get::<FatArrow>().unwrap().is_ok());
+        assert!(lexer.peek::<Arrow>().is_none());
+        assert!(lexer.get::<Minus>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn question() {
+        let mut lexer = Lexer::<1>::new("a?.b ?? c?.5");
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<Question>().unwrap().is_ok());
+        assert!(lexer.get::<QuestionDot>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<Question>().unwrap().is_ok());
+        assert!(lexer.get::<QuestionQuestion>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<QuestionDot>().is_none());
+        assert!(lexer.get::<Question>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn assignment() {
+        let mut lexer = Lexer::<1>::new("a := b = c == d");
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<Colon>().unwrap().is_ok());
+        assert!(lexer.get::<ColonEqual>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.get::<Equal>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.peek::<Equal>().unwrap().is_ok());
+        assert!(lexer.get::<EqualEqual>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn increment() {
+        let mut lexer = Lexer::<1>::new("a++ - -b");
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(matches!(
+            lexer.get::<Plus>(),
+            Some(Err(Error::Unsupported { found: "++", .. }))
+        ));
+        let mut lexer = Lexer::<1>::new("- -b --c");
+        assert!(lexer.get::<Minus>().unwrap().is_ok());
+        assert!(lexer.get::<Minus>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        let err = lexer.get::<Minus>().unwrap().err().unwrap();
+        assert!(matches!(err, Error::Unsupported { found: "--", .. }));
+    }
+
+    #[test]
+    fn empty_radix() {
+        for input in ["0x", "0o8", "0b;"] {
+            assert!(matches!(
+                Lexer::<1>::new(input).get::<Number>(),
+                Some(Err(Error::InvalidLiteral { span, .. })) if span == (0..2)
+            ));
+        }
+        for (input, span) in [("0b102", 4..5), ("0o78", 3..4), ("0x1g", 3..4)] {
+            assert!(matches!(
+                Lexer::<1>::new(input).get::<Number>(),
+                Some(Err(Error::InvalidLiteral { span: found, .. })) if found == span
+            ));
+        }
+        assert!(Lexer::<1>::new("0x1f+").get::<Number>().unwrap().is_ok());
+    }
+
+    #[test]
+    fn malformed_decimal() {
+        let span_of = |input| match Lexer::<1>::new(input).get::<Number>() {
+            Some(Err(Error::InvalidLiteral { span, .. })) => Some(span.into()),
+            _ => None,
+        };
+        assert_eq!(span_of("1."), Some(1..2));
+        assert_eq!(span_of("12. + 3"), Some(2..3));
+        assert_eq!(span_of("1.2.3"), Some(3..5));
+        assert_eq!(span_of("1.2.3.4 + 5"), Some(3..7));
+        assert_eq!(span_of("1.5"), None);
+        assert_eq!(span_of("1..5"), None);
+        assert_eq!(span_of("1.max"), None);
+        assert_eq!(span_of("0b102"), Some(4..5));
+        assert_eq!(span_of("0x1g"), Some(3..4));
+    }
+
+    #[test]
+    fn operator() {
+        let mut lexer = Lexer::<1>::new("a <|> b >>= c");
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        let op = lexer.get::<Operator>().unwrap().unwrap();
+        assert_eq!(op.eval(&lexer), "<|>");
+        assert!(lexer.get::<Operator>().is_none());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        let op = lexer.get::<Operator>().unwrap().unwrap();
+        assert_eq!(op.eval(&lexer), ">>=");
+    }
+
+    #[test]
+    fn unicode_operators() {
+        let mut lexer = Lexer::<1>::new("αβ ≤ γ·δ → x ≠ 1 ≥≠");
+        let alpha_beta = lexer.get::<Ident>().unwrap().unwrap();
+        assert_eq!(alpha_beta.eval(&lexer), "αβ");
+        assert!(lexer.get::<LessThanOrEqualTo>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.get::<MiddleDot>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.get::<RightwardsArrow>().unwrap().is_ok());
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert!(lexer.get::<NotEqualTo>().unwrap().is_ok());
+        assert!(lexer.get::<Number>().unwrap().is_ok());
+        let op = lexer.get::<Operator>().unwrap().unwrap();
+        assert_eq!(op.eval(&lexer), "≥≠");
+    }
+
+    #[test]
+    fn positions() {
+        let mut lexer = Lexer::<2>::new("a +\n  é(b)");
+        let start = lexer.current_offset();
+        assert_eq!(lexer.span_since(start), 0..0);
+        lexer.get::<Ident>().unwrap().unwrap();
+        assert!(lexer.peek::<Plus>().unwrap().is_ok());
+        assert_eq!(lexer.current_offset(), 2);
+        assert_eq!(lexer.current_line_col(), (1, 3));
+        lexer.get::<Plus>().unwrap().unwrap();
+        assert_eq!(lexer.current_line_col(), (2, 3));
+        lexer.get::<Ident>().unwrap().unwrap();
+        assert_eq!(lexer.current_line_col(), (2, 4));
+        assert_eq!(lexer.span_since(start), 0..8);
+
+        let mut lexer = Lexer::<1>::new(" \n a");
+        assert_eq!(lexer.current_offset(), 3);
+        assert_eq!(lexer.current_line_col(), (2, 2));
+        let start = lexer.current_offset();
+        assert!(lexer.get::<Ident>().unwrap().is_ok());
+        assert_eq!(lexer.span_since(start), 3..4);
+    }
+
+    #[test]
+    fn heredoc() {
+        let mut lexer = Lexer::<1>::new("<<EOF\nline\n  EOF\nEOF\n<<X\nX\n<<EOF");
+        let heredoc = lexer.get::<Heredoc>().unwrap().unwrap();
+        assert_eq!(heredoc.eval(&lexer), "line\n  EOF");
+        assert_eq!(lexer.get::<Heredoc>().unwrap().unwrap().eval(&lexer), "");
+        assert!(lexer.get::<Heredoc>().is_none());
+        assert!(lexer.get::<LessLess>().unwrap().is_ok());
+        assert!(Lexer::<1>::new("<<EOF)\nbody\nEOF")
+            .get::<Heredoc>()
+            .is_none());
+
+        let mut lexer = Lexer::<1>::new("<<EOF\nEOFA\n");
+        assert!(matches!(
+            lexer.get::<Heredoc>(),
+            Some(Err(Error::Unterminated {
+                span,
+                expected: "EOF",
+            })) if span == (0..5)
+        ));
+
+        let mut lexer = Lexer::<1>::new("<<EOF\r\nhi\r\nthere\r\nEOF\r\n<<X\r\nX\r\n");
+        let heredoc = lexer.get::<Heredoc>().unwrap().unwrap();
+        assert_eq!(heredoc.span(), &(0..21));
+        assert_eq!(heredoc.eval(&lexer), "hi\r\nthere");
+        assert_eq!(lexer.get::<Heredoc>().unwrap().unwrap().eval(&lexer), "");
+    }
+
+    #[test]
+    fn byte_string() {
+        let mut lexer = Lexer::<1>::new(r#"b"a\x41\"\n" b"é""#);
+        let bytes = lexer.get::<ByteString>().unwrap().unwrap();
+        assert!(bytes.eval(&lexer).eq(*b"aA\"\n"));
+        assert!(matches!(
+            lexer.get::<ByteString>(),
+            Some(Err(Error::InvalidLiteral {
+                expected: "ASCII",
+                ..
+            }))
+        ));
+        let input = r#"b"é""#;
+        match Lexer::<1>::new(input).get::<ByteString>() {
+            Some(Err(Error::InvalidLiteral { span, .. })) => {
+                assert_eq!(span, 2..4);
+                assert_eq!(&input[span], "é");
+            }
+            _ => panic!("expected an invalid literal"),
+        }
+        for input in [r#"b"\q""#, r#"b"\x+f""#, r#"b"\xf""#] {
+            assert!(matches!(
+                Lexer::<1>::new(input).get::<ByteString>(),
+                Some(Err(Error::InvalidLiteral { span, .. })) if span == (2..3)
+            ));
+        }
+        assert!(matches!(
+            Lexer::<1>::new(r#"b"a"#).get::<ByteString>(),
+            Some(Err(Error::Unterminated { span, .. })) if span == (0..2)
+        ));
+        assert!(Lexer::<1>::new("b").get::<ByteString>().is_none());
+    }
+
+    #[cfg(feature = "duration")]
+    #[test]
+    fn duration() {
+        use core::time::Duration as Std;
+        let mut lexer = Lexer::<1>::new("1h30m 250ms 5s10ns 5min 5m5 5s1m");
+        let duration = lexer.get::<Duration>().unwrap().unwrap();
+        assert_eq!(duration.eval(&lexer), Std::from_secs(5400));
+        let duration = lexer.get::<Duration>().unwrap().unwrap();
+        assert_eq!(duration.eval(&lexer), Std::from_millis(250));
+        let duration = lexer.get::<Duration>().unwrap().unwrap();
+        assert_eq!(duration.eval(&lexer), Std::new(5, 10));
+        assert!(lexer.get::<Duration>().is_none());
+        lexer.get::<Number>().unwrap().unwrap();
+        lexer.get::<Ident>().unwrap().unwrap();
+        assert!(lexer.get::<Duration>().is_none());
+        lexer.get::<Number>().unwrap().unwrap();
+        lexer.get::<Ident>().unwrap().unwrap();
+        lexer.get::<Number>().unwrap().unwrap();
+        assert!(matches!(
+            lexer.get::<Duration>(),
+            Some(Err(Error::InvalidLiteral { span, .. })) if span == (30..32)
+        ));
+
+        let mut lexer = Lexer::<1>::new("99999999999999999999999h");
+        let duration = lexer.get::<Duration>().unwrap().unwrap();
+        assert!(duration.eval_checked(&lexer).is_err());
+    }
+
+    #[test]
+    fn ident_policy() {
+        struct Kebab;
+        impl IdentPolicy for Kebab {
+            fn is_start(c: char) -> bool {
+                c == '$' || c.is_alphabetic()
+            }
+            fn is_continue(c: char) -> bool {
+                c == '-' || c == '\'' || c.is_alphanumeric()
+            }
+        }
+        let mut lexer = Lexer::<1>::new("$foo-bar x' -a");
+        let ident = lexer.get::<PolicyIdent<Kebab>>().unwrap().unwrap();
+        assert_eq!(ident.eval(&lexer), "$foo-bar");
+        let ident = lexer.get::<PolicyIdent<Kebab>>().unwrap().unwrap();
+        assert_eq!(ident.eval(&lexer), "x'");
+        assert!(lexer.get::<PolicyIdent<Kebab>>().is_none());
+        assert!(lexer.get::<Minus>().unwrap().is_ok());
+        let ident = lexer.get::<PolicyIdent<Alphabetic>>().unwrap().unwrap();
+        assert_eq!(ident.eval(&lexer), "a");
+    }
+
+    #[test]
+    fn keywords() {
+        let mut lexer = Lexer::<1>::with_keywords("let x = fn", &["let", "fn"]);
+        assert!(lexer.get_ident().is_none());
+        assert!(lexer.get_keyword("fn").is_none());
+        let ident = lexer.get_keyword("let").unwrap();
+        assert_eq!(ident.keyword(&lexer), Some("let"));
+        assert!(lexer.get_keyword("x").is_none());
+        let ident = lexer.get_ident().unwrap();
+        assert_eq!(ident.eval(&lexer), "x");
+        assert_eq!(ident.keyword(&lexer), None);
+        assert!(lexer.get::<Equal>().unwrap().is_ok());
+        assert!(lexer.get_ident().is_none());
+        assert!(lexer.get_keyword("fn").is_some());
+        assert!(lexer.get_keyword("fn").is_none());
+
+        let mut lexer = Lexer::<1>::new("let");
+        assert!(lexer.get_keyword("let").is_none());
+        assert!(lexer.get_ident().is_some());
+    }
+
+    #[test]
+    fn label() {
+        let mut lexer = Lexer::<1>::new("'outer: 'a' '");
+        let label = lexer.get::<Label>().unwrap().unwrap();
+        assert_eq!(label.eval(&lexer), "outer");
+        assert_eq!(label.span(), &(0..6));
+        assert!(lexer.get::<Colon>().unwrap().is_ok());
+        assert!(lexer.get::<Label>().is_none());
+        assert!(Lexer::<1>::new("'").get::<Label>().is_none());
+    }
+
+    static ONCE_PARSES: AtomicUsize = AtomicUsize::new(0);
+    struct Once(Span);
+    impl Token for Once {
+        fn span(&self) -> &Span {
+            &self.0
+        }
+        fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+        where
+            Self: Sized,
+        {
+            let (ident, len) = Ident::parse(start, input)?.ok()?;
+            ONCE_PARSES.fetch_add(1, Ordering::Relaxed);
+            Some(Ok((Self(*ident.span()), len)))
+        }
+    }
+
+    #[test]
+    fn fill_lexes_once() {
+        let registry = [TokenKind::of::<Once>(), TokenKind::of::<Plus>()];
+        let mut lexer = Lexer::<2>::new("a + b");
+        assert!(lexer.peek_n_with::<Plus>(1, &registry).unwrap().is_ok());
+        assert_eq!(ONCE_PARSES.load(Ordering::Relaxed), 1);
+        assert!(lexer.get::<Once>().unwrap().is_ok());
+
+        let registry = [TokenKind::of::<Plus>(), TokenKind::of::<Big>()];
+        assert!(matches!(
+            Lexer::<2>::new("a + b").try_peek_n_with::<Plus>(1, &registry),
+            Some(Err(Error::TokenTooLarge { .. }))
+        ));
+    }
 }
@@ -0,0 +1,46 @@
+use crate::error::Result;
+use crate::lexer::{lexed::Lexed, Lexer, TokenKind};
+use crate::span::Span;
+
+/// A fixed-capacity [`TokenBuffer`](super::buffer::TokenBuffer), for inputs
+/// of bounded size on targets without a heap.
+pub type TokenArray<'a, const N: usize> = Lexed<'a, heapless::Vec<u16, N>, heapless::Vec<Span, N>>;
+
+impl<'a, const LOOKAHEAD: usize> Lexer<'a, LOOKAHEAD> {
+    /// Like [`tokenize_all`](Lexer::tokenize_all), but without allocating.
+    /// Returns [`Error::CapacityExceeded`](crate::error::Error::CapacityExceeded)
+    /// if the input has more than `N` tokens.
+    ///
+    /// ## Panics
+    /// Panics if `registry` has more than `u16::MAX` kinds.
+    pub fn tokenize_array<'r, const N: usize>(
+        self,
+        registry: &'r [TokenKind],
+    ) -> Result<'a, TokenArray<'r, N>>
+    where
+        'a: 'r,
+    {
+        self.tokenize_into(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::lexer::tokens::*;
+
+    #[test]
+    fn tokenize_array() {
+        let registry = [TokenKind::of::<Ident>(), TokenKind::of::<Plus>()];
+        let array = Lexer::<1>::new("a + b")
+            .tokenize_array::<3>(&registry)
+            .unwrap();
+        assert_eq!(array.kinds(), &[0, 1, 0]);
+        assert_eq!(array.text(2), "b");
+        assert!(matches!(
+            Lexer::<1>::new("a + b + c").tokenize_array::<3>(&registry),
+            Err(Error::CapacityExceeded { capacity: 3 })
+        ));
+    }
+}
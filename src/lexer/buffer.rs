@@ -0,0 +1,49 @@
+use crate::error::Result;
+use crate::lexer::{lexed::Lexed, Lexer, TokenKind};
+use crate::span::Span;
+use alloc::vec::Vec;
+
+/// An entire input, lexed up front, on the heap.
+pub type TokenBuffer<'a> = Lexed<'a, Vec<u16>, Vec<Span>>;
+
+impl<'a, const LOOKAHEAD: usize> Lexer<'a, LOOKAHEAD> {
+    /// Lexes the rest of the input with `registry`.
+    ///
+    /// At each position the longest match wins, with ties going to whichever
+    /// kind comes first in `registry`.
+    ///
+    /// ## Panics
+    /// Panics if `registry` has more than `u16::MAX` kinds.
+    pub fn tokenize_all<'r>(self, registry: &'r [TokenKind]) -> Result<'a, TokenBuffer<'r>>
+    where
+        'a: 'r,
+    {
+        self.tokenize_into(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{tokens::*, Token};
+
+    #[test]
+    fn tokenize_all() {
+        let registry = [
+            TokenKind::of::<Ident>(),
+            TokenKind::of::<Number>(),
+            TokenKind::of::<Plus>(),
+            TokenKind::of::<PlusEqual>(),
+        ];
+        let lexer = Lexer::<1>::new(" a += 12 + b ");
+        let buffer = lexer.tokenize_all(&registry).unwrap();
+        assert_eq!(buffer.kinds(), &[0, 3, 1, 2, 0]);
+        assert_eq!(buffer.spans(), &[1..2, 3..5, 6..8, 9..10, 11..12]);
+        assert_eq!(buffer.text(2), "12");
+        assert!(buffer.get::<Plus>(1).is_none());
+        assert_eq!(buffer.get::<Plus>(3).unwrap().span(), &(9..10));
+
+        let lexer = Lexer::<1>::new("a ; b");
+        assert!(lexer.tokenize_all(&registry).is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use crate::error::{Error, Result};
+use crate::lexer::{Lexer, Token, TokenKind};
+use crate::span::Span;
+use core::ops::Deref;
+
+/// A growable list to store lexed tokens in.
+pub trait Storage<T>: Default + Deref<Target = [T]> {
+    /// Returns `false` if there is no room for `value`.
+    fn push(&mut self, value: T) -> bool;
+}
+impl<T, const N: usize> Storage<T> for heapless::Vec<T, N> {
+    fn push(&mut self, value: T) -> bool {
+        heapless::Vec::push(self, value).is_ok()
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T> Storage<T> for alloc::vec::Vec<T> {
+    fn push(&mut self, value: T) -> bool {
+        alloc::vec::Vec::push(self, value);
+        true
+    }
+}
+
+/// An entire input, lexed up front.
+///
+/// Tokens are stored as an index into the registry they were lexed with and
+/// a span, in separate arrays. See [`TokenBuffer`](super::buffer::TokenBuffer)
+/// and [`TokenArray`](super::array::TokenArray).
+#[derive(Debug)]
+pub struct Lexed<'a, K, S> {
+    input: &'a str,
+    registry: &'a [TokenKind],
+    kinds: K,
+    spans: S,
+}
+impl<'a, K: Storage<u16>, S: Storage<Span>> Lexed<'a, K, S> {
+    pub fn len(&self) -> usize {
+        self.kinds.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.kinds.is_empty()
+    }
+    /// The kinds of every token, as indices into the registry.
+    pub fn kinds(&self) -> &[u16] {
+        &self.kinds
+    }
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+    pub fn kind(&self, index: usize) -> &TokenKind {
+        &self.registry[self.kinds[index] as usize]
+    }
+    pub fn span(&self, index: usize) -> &Span {
+        &self.spans[index]
+    }
+    pub fn text(&self, index: usize) -> &'a str {
+        &self.input[self.spans[index]]
+    }
+    pub fn is<T: Token>(&self, index: usize) -> bool {
+        self.kind(index).is::<T>()
+    }
+    /// Returns `None` if the token at `index` is not a `T`.
+    pub fn get<T: Token>(&self, index: usize) -> Option<T> {
+        if !self.is::<T>(index) {
+            return None;
+        }
+        let start = self.spans[index].start;
+        match T::parse(start, &self.input[start..]) {
+            Some(Ok((token, _))) => Some(token),
+            _ => unreachable!("token changed between lexing and reading"),
+        }
+    }
+}
+
+impl<'a, const LOOKAHEAD: usize> Lexer<'a, LOOKAHEAD> {
+    /// Lexes the rest of the input with `registry` into `K` and `S`.
+    ///
+    /// Returns [`Error::CapacityExceeded`] if they run out of room.
+    ///
+    /// ## Panics
+    /// Panics if `registry` has more than `u16::MAX` kinds.
+    pub(crate) fn tokenize_into<'r, K, S>(
+        self,
+        registry: &'r [TokenKind],
+    ) -> Result<'a, Lexed<'r, K, S>>
+    where
+        'a: 'r,
+        K: Storage<u16>,
+        S: Storage<Span>,
+    {
+        assert!(registry.len() <= u16::MAX as usize);
+        let mut index = self.registry_start();
+        let mut kinds = K::default();
+        let mut spans = S::default();
+        while index < self.input.len() {
+            let (kind, (), len) = self.parse_kind(registry, index, |kind, start, input| {
+                Some(kind.parse(start, input)?.map(|len| ((), len)))
+            })?;
+            let capacity = kinds.len();
+            if !kinds.push(kind) || !spans.push(Span::new(index, index + len)) {
+                return Err(Error::CapacityExceeded { capacity });
+            }
+            index = self.skip_whitespace(index + len);
+        }
+        Ok(Lexed {
+            input: self.input,
+            registry,
+            kinds,
+            spans,
+        })
+    }
+}
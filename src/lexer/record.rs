@@ -0,0 +1,198 @@
+use crate::error::Result;
+use crate::lexer::{tokens::Ident, Lexer, Token, TokenKind};
+use crate::span::Span;
+use alloc::vec::Vec;
+use core::ops::Deref;
+
+/// A token consumed by a [`RecordingLexer`].
+#[derive(Debug, Clone)]
+pub struct Recorded {
+    pub kind: TokenKind,
+    pub span: Span,
+}
+
+/// Wraps a [`Lexer`], recording every token consumed through it.
+///
+/// Peeking is not recorded, only tokens returned from `get` and friends.
+pub struct RecordingLexer<'a, const LOOKAHEAD: usize> {
+    lexer: Lexer<'a, LOOKAHEAD>,
+    tokens: Vec<Recorded>,
+}
+impl<'a, const LOOKAHEAD: usize> RecordingLexer<'a, LOOKAHEAD> {
+    pub fn new(input: &'a str) -> Self {
+        Lexer::new(input).into()
+    }
+    /// Every token consumed so far, in order.
+    pub fn recorded(&self) -> &[Recorded] {
+        &self.tokens
+    }
+    pub fn into_recording(self) -> Recording<'a> {
+        Recording {
+            input: self.lexer.input,
+            tokens: self.tokens,
+        }
+    }
+    /// See [`Lexer::peek`].
+    pub fn peek<T: Token + 'static>(&mut self) -> Option<Result<'_, &T>> {
+        self.lexer.peek()
+    }
+    /// See [`Lexer::peek_n`].
+    pub fn peek_n<T: Token + 'static>(&mut self, n: usize) -> Option<Result<'_, &T>> {
+        self.lexer.peek_n(n)
+    }
+    /// See [`Lexer::peek_n_with`].
+    pub fn peek_n_with<T: Token + 'static>(
+        &mut self,
+        n: usize,
+        registry: &[TokenKind],
+    ) -> Option<Result<'_, &T>> {
+        self.lexer.peek_n_with(n, registry)
+    }
+    /// See [`Lexer::try_peek`].
+    pub fn try_peek<T: Token + 'static>(&mut self) -> Option<Result<'_, &T>> {
+        self.lexer.try_peek()
+    }
+    /// See [`Lexer::try_peek_n`].
+    pub fn try_peek_n<T: Token + 'static>(&mut self, n: usize) -> Option<Result<'_, &T>> {
+        self.lexer.try_peek_n(n)
+    }
+    /// See [`Lexer::try_peek_n_with`].
+    pub fn try_peek_n_with<T: Token + 'static>(
+        &mut self,
+        n: usize,
+        registry: &[TokenKind],
+    ) -> Option<Result<'_, &T>> {
+        self.lexer.try_peek_n_with(n, registry)
+    }
+    /// See [`Lexer::get`].
+    pub fn get<T: Token + 'static>(&mut self) -> Option<Result<'_, T>> {
+        let token = self.lexer.get::<T>();
+        if let Some(Ok(token)) = &token {
+            Self::record(&mut self.tokens, token);
+        }
+        token
+    }
+    /// See [`Lexer::try_get`].
+    pub fn try_get<T: Token + 'static>(&mut self) -> Option<Result<'_, T>> {
+        let token = self.lexer.try_get::<T>();
+        if let Some(Ok(token)) = &token {
+            Self::record(&mut self.tokens, token);
+        }
+        token
+    }
+    /// See [`Lexer::get_buffered`].
+    pub fn get_buffered<T: Token + 'static>(&mut self) -> Option<T> {
+        let token = self.lexer.get_buffered::<T>()?;
+        Self::record(&mut self.tokens, &token);
+        Some(token)
+    }
+    /// See [`Lexer::get_ident`].
+    pub fn get_ident(&mut self) -> Option<Ident> {
+        let token = self.lexer.get_ident()?;
+        Self::record(&mut self.tokens, &token);
+        Some(token)
+    }
+    /// See [`Lexer::get_keyword`].
+    pub fn get_keyword(&mut self, keyword: &str) -> Option<Ident> {
+        let token = self.lexer.get_keyword(keyword)?;
+        Self::record(&mut self.tokens, &token);
+        Some(token)
+    }
+    fn record<T: Token>(tokens: &mut Vec<Recorded>, token: &T) {
+        tokens.push(Recorded {
+            kind: TokenKind::of::<T>(),
+            span: *token.span(),
+        });
+    }
+}
+impl<'a, const LOOKAHEAD: usize> From<Lexer<'a, LOOKAHEAD>> for RecordingLexer<'a, LOOKAHEAD> {
+    fn from(lexer: Lexer<'a, LOOKAHEAD>) -> Self {
+        Self {
+            lexer,
+            tokens: Vec::new(),
+        }
+    }
+}
+impl<'a, const LOOKAHEAD: usize> Deref for RecordingLexer<'a, LOOKAHEAD> {
+    type Target = Lexer<'a, LOOKAHEAD>;
+    fn deref(&self) -> &Self::Target {
+        &self.lexer
+    }
+}
+
+/// The tokens consumed from a [`RecordingLexer`], along with their input.
+#[derive(Debug, Clone)]
+pub struct Recording<'a> {
+    input: &'a str,
+    tokens: Vec<Recorded>,
+}
+impl<'a> Recording<'a> {
+    pub fn input(&self) -> &'a str {
+        self.input
+    }
+    pub fn tokens(&self) -> &[Recorded] {
+        &self.tokens
+    }
+    pub fn replay(&self) -> Replay<'_> {
+        Replay {
+            recording: self,
+            index: 0,
+        }
+    }
+}
+
+/// Steps through a [`Recording`], handing back the same tokens in the same
+/// order.
+#[derive(Debug, Clone)]
+pub struct Replay<'r> {
+    recording: &'r Recording<'r>,
+    index: usize,
+}
+impl Replay<'_> {
+    /// The next recorded token, without consuming it.
+    pub fn peek(&self) -> Option<&Recorded> {
+        self.recording.tokens.get(self.index)
+    }
+    /// Consumes the next recorded token if it is a `T`.
+    pub fn get<T: Token>(&mut self) -> Option<T> {
+        let recorded = self.peek()?;
+        if !recorded.kind.is::<T>() {
+            return None;
+        }
+        let start = recorded.span.start;
+        let token = match T::parse(start, &self.recording.input[start..]) {
+            Some(Ok((token, _))) => token,
+            _ => unreachable!("token changed between recording and replay"),
+        };
+        self.index += 1;
+        Some(token)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::tokens::*;
+
+    #[test]
+    fn record_and_replay() {
+        let mut lexer = RecordingLexer::<2>::new("a + 1");
+        assert!(lexer.peek_n::<Ident>(0).unwrap().is_ok());
+        assert!(lexer.peek_n::<Plus>(1).unwrap().is_ok());
+        let a = lexer.get::<Ident>().unwrap().unwrap();
+        assert_eq!(a.eval(&lexer), "a");
+        assert!(lexer.get_buffered::<Plus>().is_some());
+        assert!(lexer.get::<Ident>().is_none());
+        assert!(lexer.get::<Number>().unwrap().is_ok());
+        assert_eq!(lexer.recorded().len(), 3);
+
+        let recording = lexer.into_recording();
+        let mut replay = recording.replay();
+        assert!(replay.get::<Plus>().is_none());
+        assert_eq!(replay.get::<Ident>().unwrap().span(), &(0..1));
+        assert_eq!(replay.peek().unwrap().span, 2..3);
+        assert!(replay.get::<Plus>().is_some());
+        assert!(replay.get::<Number>().is_some());
+        assert!(replay.peek().is_none());
+    }
+}
@@ -1,30 +1,49 @@
+use crate::error::{Error, LiteralError, Result};
 use crate::lexer::Lexer;
-use crate::{error::Result, lexer::Token};
-use core::ops::Range;
+use crate::lexer::Token;
+use crate::span::Span;
+use core::marker::PhantomData;
 use core::str::FromStr;
 
+/// Defines a token for fixed text.
+///
+/// Every token matches the start of longer ones: `<` matches in `<<=` and
+/// `&` in `&&`. A parser asks for the longer token first, or takes the
+/// shorter one to split it, e.g. `>>` closing two generic lists. `!x` is only
+/// for text that isn't an operator at all when followed by `x`, and `reject`
+/// turns text from other languages into [`Error::Unsupported`].
 macro_rules! simple_token {
-    ($token:expr => $name:ident) => {
-        pub struct $name(::core::ops::Range<usize>);
+    ($token:expr => $name:ident $(, !$not:expr)* $(, reject $bad:expr => $help:expr)*) => {
+        pub struct $name($crate::span::Span);
         impl $crate::lexer::Token for $name {
-            fn span(&self) -> &::core::ops::Range<usize> {
+            fn span(&self) -> &$crate::span::Span {
                 &self.0
             }
-            fn parse(start: usize, input: &str) -> Option<$crate::error::Result<(Self, usize)>>
+            fn parse(start: usize, input: &str) -> Option<$crate::error::Result<'_, (Self, usize)>>
             where
                 Self: Sized,
             {
-                input.strip_prefix($token).map(|new| {
-                    let consumed = input.len() - new.len();
-                    Ok((Self(start..start + consumed), consumed))
-                })
+                $(
+                    if input.starts_with($bad) {
+                        return Some(Err($crate::error::Error::Unsupported {
+                            found: &input[..$bad.len()],
+                            help: $help,
+                        }));
+                    }
+                )*
+                let new = input.strip_prefix($token)?;
+                if false $(|| new.starts_with($not))* {
+                    return None;
+                }
+                let consumed = input.len() - new.len();
+                Some(Ok((Self(Span::new(start, start + consumed)), consumed)))
             }
         }
     };
 }
 
-simple_token!('+' => Plus);
-simple_token!('-' => Minus);
+simple_token!('+' => Plus, reject "++" => "use `+= 1`");
+simple_token!('-' => Minus, reject "--" => "use `-= 1`");
 simple_token!('*' => Star);
 simple_token!('/' => Slash);
 simple_token!('%' => Percent);
@@ -42,7 +61,20 @@ simple_token!(">=" => GreaterEqual);
 simple_token!("&&" => AndAnd);
 simple_token!("||" => OrOr);
 
+simple_token!('&' => And);
+simple_token!('|' => Or);
+simple_token!('^' => Caret);
+simple_token!('~' => Tilde);
+simple_token!("<<" => LessLess);
+simple_token!(">>" => GreaterGreater);
+simple_token!("&=" => AndEqual);
+simple_token!("|=" => OrEqual);
+simple_token!("^=" => CaretEqual);
+simple_token!("<<=" => LessLessEqual);
+simple_token!(">>=" => GreaterGreaterEqual);
+
 simple_token!("!" => Bang);
+simple_token!('=' => Equal);
 simple_token!(":=" => ColonEqual);
 simple_token!("<" => Less);
 simple_token!(">" => Greater);
@@ -50,7 +82,22 @@ simple_token!(">" => Greater);
 simple_token!(";" => Semicolon);
 simple_token!("," => Comma);
 simple_token!("." => Dot);
+simple_token!(".." => DotDot);
+simple_token!("..=" => DotDotEqual);
 simple_token!(':' => Colon);
+simple_token!("->" => Arrow);
+simple_token!("=>" => FatArrow);
+
+// `a?.5:b` is a conditional, not optional chaining
+simple_token!('?' => Question);
+simple_token!("?." => QuestionDot, !|c: char| c.is_ascii_digit());
+simple_token!("??" => QuestionQuestion);
+
+simple_token!('≤' => LessThanOrEqualTo);
+simple_token!('≥' => GreaterThanOrEqualTo);
+simple_token!('≠' => NotEqualTo);
+simple_token!('→' => RightwardsArrow);
+simple_token!('·' => MiddleDot);
 
 simple_token!("(" => LeftParen);
 simple_token!(")" => RightParen);
@@ -59,66 +106,589 @@ simple_token!("}" => RightBrace);
 simple_token!("[" => LeftBracket);
 simple_token!("]" => RightBracket);
 
-pub struct Ident(Range<usize>);
+pub struct Ident(Span);
 impl Ident {
     pub fn eval<'a, const LOOKAHEAD: usize>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> &'a str {
-        &lexer.input[self.0.clone()]
+        &lexer.input[self.0]
+    }
+    /// The keyword this identifier is, if it's in the lexer's
+    /// [`keywords`](Lexer::keywords).
+    pub fn keyword<'a, const LOOKAHEAD: usize>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+    ) -> Option<&'a str> {
+        let text = self.eval(lexer);
+        lexer
+            .keywords
+            .iter()
+            .copied()
+            .find(|&keyword| keyword == text)
     }
 }
 impl Token for Ident {
-    fn span(&self) -> &Range<usize> {
+    fn span(&self) -> &Span {
         &self.0
     }
-    fn parse(start: usize, input: &str) -> Option<Result<(Self, usize)>>
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
     where
         Self: Sized,
     {
         input.strip_prefix(char::is_alphabetic).map(|new: &str| {
             let consumed = input.len() - new.len()
-                + new.chars().take_while(|c: &char| c.is_alphabetic()).count();
-            Ok((Self(start..start + consumed), consumed))
+                + new
+                    .chars()
+                    .take_while(|c: &char| c.is_alphabetic())
+                    .map(char::len_utf8)
+                    .sum::<usize>();
+            Ok((Self(Span::new(start, start + consumed)), consumed))
         })
     }
 }
 
-pub struct Number(Range<usize>);
+/// A loop label, like `'outer`. A trailing `'`, as in `'a'`, is not a label.
+pub struct Label(Span);
+impl Label {
+    /// The label's name, without the `'`.
+    pub fn eval<'a, const LOOKAHEAD: usize>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> &'a str {
+        &lexer.input[self.0][1..]
+    }
+}
+impl Token for Label {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        let rest = input.strip_prefix('\'')?;
+        let (name, len) = Ident::parse(start + 1, rest)?.ok()?;
+        if rest[len..].starts_with('\'') {
+            return None;
+        }
+        let consumed = 1 + len;
+        Some(Ok((Self(Span::new(start, name.span().end)), consumed)))
+    }
+}
+
+/// Decides which characters make up a [`PolicyIdent`].
+pub trait IdentPolicy: 'static {
+    fn is_start(c: char) -> bool;
+    fn is_continue(c: char) -> bool;
+}
+/// The policy [`Ident`] uses: one or more alphabetic characters.
+pub struct Alphabetic;
+impl IdentPolicy for Alphabetic {
+    fn is_start(c: char) -> bool {
+        c.is_alphabetic()
+    }
+    fn is_continue(c: char) -> bool {
+        c.is_alphabetic()
+    }
+}
+
+/// An identifier whose characters are decided by `P`, for languages that
+/// allow e.g. `kebab-case` or `$prefixed` names.
+pub struct PolicyIdent<P>(Span, PhantomData<P>);
+impl<P> PolicyIdent<P> {
+    pub fn eval<'a, const LOOKAHEAD: usize>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> &'a str {
+        &lexer.input[self.0]
+    }
+}
+impl<P: IdentPolicy> Token for PolicyIdent<P> {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        let first = input.chars().next().filter(|&c| P::is_start(c))?;
+        let consumed = first.len_utf8()
+            + input[first.len_utf8()..]
+                .chars()
+                .take_while(|&c| P::is_continue(c))
+                .map(char::len_utf8)
+                .sum::<usize>();
+        Some(Ok((
+            Self(Span::new(start, start + consumed), PhantomData),
+            consumed,
+        )))
+    }
+}
+
+pub struct Number(Span);
 impl Number {
+    /// ## Panics
+    /// Panics if the literal can't be evaluated as `T`. See
+    /// [`eval_checked`](Number::eval_checked).
     pub fn eval<'a, const LOOKAHEAD: usize, T>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> T
+    where
+        T: TryFrom<u128> + TryFrom<i128>,
+    {
+        match self.eval_checked(lexer) {
+            Ok(value) => value,
+            Err(err) => panic!("{err}"),
+        }
+    }
+    pub fn eval_checked<'a, const LOOKAHEAD: usize, T>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+    ) -> core::result::Result<T, LiteralError>
+    where
+        T: TryFrom<u128> + TryFrom<i128>,
+    {
+        let ty = core::any::type_name::<T>();
+        let magnitude = self.magnitude(lexer, ty)?;
+        T::try_from(magnitude).map_err(|_| LiteralError::OutOfRange { span: self.0, ty })
+    }
+    /// Evaluates the literal with a unary minus applied, so that e.g.
+    /// `-128` fits in an `i8`.
+    pub fn eval_negated_checked<'a, const LOOKAHEAD: usize, T>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+    ) -> core::result::Result<T, LiteralError>
+    where
+        T: TryFrom<u128> + TryFrom<i128>,
+    {
+        let ty = core::any::type_name::<T>();
+        let magnitude = self.magnitude(lexer, ty)?;
+        let out_of_range = || LiteralError::OutOfRange { span: self.0, ty };
+        if magnitude == 0 {
+            return T::try_from(0u128).map_err(|_| out_of_range());
+        }
+        if T::try_from(-1i128).is_err() {
+            return Err(LiteralError::Negative { span: self.0, ty });
+        }
+        if magnitude > i128::MIN.unsigned_abs() {
+            return Err(out_of_range());
+        }
+        T::try_from((magnitude as i128).wrapping_neg()).map_err(|_| out_of_range())
+    }
+    /// Evaluates a literal with a fraction, like `1.5`.
+    ///
+    /// ## Panics
+    /// Panics if `T` can't be parsed from the literal.
+    pub fn eval_float<'a, const LOOKAHEAD: usize, T>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> T
     where
         T: FromStr,
         T::Err: core::fmt::Debug,
     {
-        lexer.input[self.0.clone()].parse().unwrap()
+        lexer.input[self.0].parse().unwrap()
+    }
+    fn magnitude<'a, const LOOKAHEAD: usize>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+        ty: &'static str,
+    ) -> core::result::Result<u128, LiteralError> {
+        let text = &lexer.input[self.0];
+        let (digits, radix) = if let Some(digits) = text.strip_prefix("0x") {
+            (digits, 16)
+        } else if let Some(digits) = text.strip_prefix("0o") {
+            (digits, 8)
+        } else if let Some(digits) = text.strip_prefix("0b") {
+            (digits, 2)
+        } else {
+            (text, 10)
+        };
+        if digits.contains('.') {
+            return Err(LiteralError::NotAnInteger { span: self.0 });
+        }
+        u128::from_str_radix(digits, radix)
+            .map_err(|_| LiteralError::OutOfRange { span: self.0, ty })
     }
 }
 impl Token for Number {
-    fn span(&self) -> &Range<usize> {
+    fn span(&self) -> &Span {
         &self.0
     }
-    fn parse(start: usize, input: &str) -> Option<Result<(Self, usize)>>
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
     where
         Self: Sized,
     {
-        let mut chars = input.chars();
-        match chars.next() {
-            Some('0') if chars.next() == Some('x') => {
-                let consumed = input.len() - chars.as_str().len()
-                    + chars.take_while(|c: &char| c.is_ascii_hexdigit()).count();
-                Some(Ok((Self(start..start + consumed), consumed)))
+        let digits = |is_digit: fn(&u8) -> bool, expected: &'static str| {
+            let count = input.as_bytes()[2..]
+                .iter()
+                .take_while(|c| is_digit(c))
+                .count();
+            if count == 0 {
+                return Some(Err(Error::InvalidLiteral {
+                    span: Span::new(start, start + 2),
+                    expected,
+                }));
             }
-            Some('0') if chars.next() == Some('b') => {
-                let consumed = input.len() - chars.as_str().len()
-                    + chars.take_while(|c: &char| c == &'0' || c == &'1').count();
-                Some(Ok((Self(start..start + consumed), consumed)))
+            let consumed = 2 + count;
+            if let Some(c) = input[consumed..].chars().next() {
+                if c.is_alphanumeric() {
+                    return Some(Err(Error::InvalidLiteral {
+                        span: Span::new(start + consumed, start + consumed + c.len_utf8()),
+                        expected,
+                    }));
+                }
             }
-            Some(c) if c.is_ascii_digit() => {
-                let consumed = input.len() - chars.as_str().len()
-                    + chars
-                        .take_while(|c: &char| c.is_ascii_digit() || c == &'.')
+            Some(Ok((Self(Span::new(start, start + consumed)), consumed)))
+        };
+        let bytes = input.as_bytes();
+        match bytes {
+            [b'0', b'x', ..] => digits(u8::is_ascii_hexdigit, "hexadecimal digits"),
+            [b'0', b'o', ..] => digits(|c| (b'0'..=b'7').contains(c), "octal digits"),
+            [b'0', b'b', ..] => digits(|c| c == &b'0' || c == &b'1', "binary digits"),
+            [c, ..] if c.is_ascii_digit() => {
+                let digits_from = |from: usize| {
+                    from + bytes[from..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit())
+                        .count()
+                };
+                let mut consumed = digits_from(0);
+                if bytes.get(consumed) == Some(&b'.') {
+                    let after = &input[consumed + 1..];
+                    match after.chars().next() {
+                        Some(c) if c.is_ascii_digit() => consumed = digits_from(consumed + 1),
+                        // a range, or a field or method on an integer
+                        Some(c) if c == '.' || c == '_' || c.is_alphabetic() => {}
+                        _ => {
+                            return Some(Err(Error::InvalidLiteral {
+                                span: Span::new(start + consumed, start + consumed + 1),
+                                expected: "digits after `.`",
+                            }))
+                        }
+                    }
+                }
+                if bytes.get(consumed) == Some(&b'.')
+                    && bytes.get(consumed + 1).is_some_and(u8::is_ascii_digit)
+                {
+                    let extra = bytes[consumed..]
+                        .iter()
+                        .take_while(|c| c.is_ascii_digit() || c == &&b'.')
                         .count();
-                Some(Ok((Self(start..start + consumed), consumed)))
+                    return Some(Err(Error::InvalidLiteral {
+                        span: Span::new(start + consumed, start + consumed + extra),
+                        expected: "a single decimal point",
+                    }));
+                }
+                Some(Ok((Self(Span::new(start, start + consumed)), consumed)))
             }
             _ => None,
         }
     }
 }
+
+/// A `/// ...` line or `/** ... */` block doc comment.
+pub struct DocComment(Span);
+impl DocComment {
+    /// The comment's text, without the comment markers.
+    pub fn eval<'a, const LOOKAHEAD: usize>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> &'a str {
+        let text = &lexer.input[self.0];
+        match text.strip_prefix("///") {
+            Some(line) => line,
+            None => &text[3..text.len() - 2],
+        }
+    }
+}
+impl Token for DocComment {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        if let Some(line) = input.strip_prefix("///") {
+            if line.starts_with('/') {
+                return None;
+            }
+            let line = &line[..line.find('\n').unwrap_or(line.len())];
+            let consumed = 3 + line.strip_suffix('\r').unwrap_or(line).len();
+            return Some(Ok((Self(Span::new(start, start + consumed)), consumed)));
+        }
+        let block = input.strip_prefix("/**")?;
+        if block.starts_with('*') || block.starts_with('/') {
+            return None;
+        }
+        match block.find("*/") {
+            Some(end) => {
+                let consumed = 3 + end + 2;
+                Some(Ok((Self(Span::new(start, start + consumed)), consumed)))
+            }
+            None => Some(Err(Error::Unterminated {
+                span: Span::new(start, start + 3),
+                expected: "`*/`",
+            })),
+        }
+    }
+}
+
+/// A run of operator characters, for languages that let users define their
+/// own operators. Which text is a valid operator is up to the parser.
+pub struct Operator(Span);
+impl Operator {
+    pub const CHARS: &'static str = "!#$%&*+-./:<=>?@\\^|~≤≥≠→·×÷∘";
+
+    pub fn eval<'a, const LOOKAHEAD: usize>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> &'a str {
+        &lexer.input[self.0]
+    }
+}
+impl Token for Operator {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        let consumed = input
+            .chars()
+            .take_while(|&c| Self::CHARS.contains(c))
+            .map(char::len_utf8)
+            .sum();
+        if consumed == 0 {
+            return None;
+        }
+        Some(Ok((Self(Span::new(start, start + consumed)), consumed)))
+    }
+}
+
+/// A `<<TAG` heredoc, running over every following line up to one that is
+/// exactly `TAG`. Lines can end in `\n` or `\r\n`.
+///
+/// Only the line-final form is supported: `<<TAG` has to end its line. A
+/// heredoc in the middle of a line, like `print(<<EOF)`, would need the lexer
+/// to defer the body until the next newline, which tokens can't do since they
+/// don't see the lexer's state.
+pub struct Heredoc(Span);
+impl Heredoc {
+    /// The lines between the opening tag and the terminator.
+    pub fn eval<'a, const LOOKAHEAD: usize>(&self, lexer: &'a Lexer<'a, LOOKAHEAD>) -> &'a str {
+        let text = &lexer.input[self.0];
+        let (open, rest) = text.split_once('\n').unwrap();
+        let tag_len = open.trim_end_matches('\r').len() - 2;
+        let body = &rest[..rest.len() - tag_len];
+        let body = body.strip_suffix('\n').unwrap_or(body);
+        body.strip_suffix('\r').unwrap_or(body)
+    }
+}
+impl Token for Heredoc {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        let rest = input.strip_prefix("<<")?;
+        let tag_len = rest
+            .chars()
+            .take_while(|c| c.is_alphabetic())
+            .map(char::len_utf8)
+            .sum();
+        if tag_len == 0 {
+            return None;
+        }
+        let (tag, rest) = rest.split_at(tag_len);
+        let body = rest
+            .strip_prefix('\n')
+            .or_else(|| rest.strip_prefix("\r\n"))?;
+        let mut consumed = input.len() - body.len();
+        for line in body.split_inclusive('\n') {
+            let text = line.strip_suffix('\n').unwrap_or(line);
+            if text.strip_suffix('\r').unwrap_or(text) == tag {
+                consumed += tag_len;
+                return Some(Ok((Self(Span::new(start, start + consumed)), consumed)));
+            }
+            consumed += line.len();
+        }
+        Some(Err(Error::Unterminated {
+            span: Span::new(start, start + 2 + tag_len),
+            expected: tag,
+        }))
+    }
+}
+
+/// A `b"..."` byte string. Only ASCII is allowed, with `\n`, `\r`, `\t`,
+/// `\0`, `\\`, `\"` and `\xHH` escapes.
+pub struct ByteString(Span);
+impl ByteString {
+    /// The bytes of the string, with escapes resolved.
+    pub fn eval<'a, const LOOKAHEAD: usize>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+    ) -> impl Iterator<Item = u8> + 'a {
+        let text = &lexer.input[self.0];
+        let mut rest = &text.as_bytes()[2..text.len() - 1];
+        core::iter::from_fn(move || {
+            let (&byte, tail) = rest.split_first()?;
+            if byte != b'\\' {
+                rest = tail;
+                return Some(byte);
+            }
+            let (byte, len) = Self::escape(tail).unwrap();
+            rest = &tail[len..];
+            Some(byte)
+        })
+    }
+    /// Resolves the escape after a `\`, returning the byte and the length of
+    /// the escape.
+    fn escape(input: &[u8]) -> Option<(u8, usize)> {
+        let byte = match input.first()? {
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'0' => b'\0',
+            b'\\' => b'\\',
+            b'"' => b'"',
+            b'x' => {
+                let digits = input.get(1..3)?;
+                if !digits.iter().all(u8::is_ascii_hexdigit) {
+                    return None;
+                }
+                let digits = core::str::from_utf8(digits).ok()?;
+                return Some((u8::from_str_radix(digits, 16).ok()?, 3));
+            }
+            _ => return None,
+        };
+        Some((byte, 1))
+    }
+}
+impl Token for ByteString {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        let bytes = input.strip_prefix("b\"")?.as_bytes();
+        let mut i = 0;
+        while let Some(&byte) = bytes.get(i) {
+            match byte {
+                b'"' => {
+                    let consumed = 2 + i + 1;
+                    return Some(Ok((Self(Span::new(start, start + consumed)), consumed)));
+                }
+                b'\\' => match Self::escape(&bytes[i + 1..]) {
+                    Some((_, len)) => i += 1 + len,
+                    None => {
+                        return Some(Err(Error::InvalidLiteral {
+                            span: Span::new(start + 2 + i, start + 2 + i + 1),
+                            expected: "escape sequence",
+                        }))
+                    }
+                },
+                _ if byte.is_ascii() => i += 1,
+                _ => {
+                    let len = input[2 + i..].chars().next().map_or(1, char::len_utf8);
+                    return Some(Err(Error::InvalidLiteral {
+                        span: Span::new(start + 2 + i, start + 2 + i + len),
+                        expected: "ASCII",
+                    }));
+                }
+            }
+        }
+        Some(Err(Error::Unterminated {
+            span: Span::new(start, start + 2),
+            expected: "`\"`",
+        }))
+    }
+}
+
+/// A duration like `1h30m` or `250ms`. Each of the units `h`, `m`, `s`, `ms`,
+/// `us` and `ns` can appear once, from largest to smallest.
+#[cfg(feature = "duration")]
+pub struct Duration(Span);
+#[cfg(feature = "duration")]
+impl Duration {
+    const UNITS: [(&'static str, u128); 6] = [
+        ("h", 3_600_000_000_000),
+        ("m", 60_000_000_000),
+        ("s", 1_000_000_000),
+        ("ms", 1_000_000),
+        ("us", 1_000),
+        ("ns", 1),
+    ];
+
+    /// ## Panics
+    /// Panics if the duration doesn't fit in a [`core::time::Duration`]. See
+    /// [`eval_checked`](Duration::eval_checked).
+    pub fn eval<'a, const LOOKAHEAD: usize>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+    ) -> core::time::Duration {
+        match self.eval_checked(lexer) {
+            Ok(value) => value,
+            Err(err) => panic!("{err}"),
+        }
+    }
+    pub fn eval_checked<'a, const LOOKAHEAD: usize>(
+        &self,
+        lexer: &'a Lexer<'a, LOOKAHEAD>,
+    ) -> core::result::Result<core::time::Duration, LiteralError> {
+        let out_of_range = || LiteralError::OutOfRange {
+            span: self.0,
+            ty: "Duration",
+        };
+        let mut text = &lexer.input[self.0];
+        let mut nanos = 0u128;
+        while !text.is_empty() {
+            let (count, unit, len) = Self::group(text).unwrap();
+            let count: u128 = text[..count].parse().map_err(|_| out_of_range())?;
+            nanos = count
+                .checked_mul(Self::UNITS[unit].1)
+                .and_then(|group| nanos.checked_add(group))
+                .ok_or_else(out_of_range)?;
+            text = &text[len..];
+        }
+        let secs = u64::try_from(nanos / 1_000_000_000).map_err(|_| out_of_range())?;
+        Ok(core::time::Duration::new(
+            secs,
+            (nanos % 1_000_000_000) as u32,
+        ))
+    }
+    /// Splits a group like `30m` off the front of `input`, returning the
+    /// number of digits, the index of the unit in `UNITS` and the length of
+    /// the group.
+    fn group(input: &str) -> Option<(usize, usize, usize)> {
+        let digits = input.bytes().take_while(u8::is_ascii_digit).count();
+        if digits == 0 {
+            return None;
+        }
+        // two-letter units first, so that `ms` isn't lexed as `m`
+        let (unit, (name, _)) = Self::UNITS
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, (name, _))| input[digits..].starts_with(name))?;
+        Some((digits, unit, digits + name.len()))
+    }
+}
+#[cfg(feature = "duration")]
+impl Token for Duration {
+    fn span(&self) -> &Span {
+        &self.0
+    }
+    fn parse(start: usize, input: &str) -> Option<Result<'_, (Self, usize)>>
+    where
+        Self: Sized,
+    {
+        let mut consumed = 0;
+        let mut next_unit = 0;
+        while let Some((_, unit, len)) = Self::group(&input[consumed..]) {
+            if unit < next_unit {
+                return Some(Err(Error::InvalidLiteral {
+                    span: Span::new(start + consumed, start + consumed + len),
+                    expected: "units from largest to smallest",
+                }));
+            }
+            next_unit = unit + 1;
+            consumed += len;
+        }
+        let followed = input[consumed..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_alphanumeric() || c == '_');
+        if consumed == 0 || followed {
+            return None;
+        }
+        Some(Ok((Self(Span::new(start, start + consumed)), consumed)))
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::{Error, Result};
+use crate::lexer::{tokens::*, Lexer, TokenKind};
+use crate::span::Span;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delimiter {
+    Paren,
+    Brace,
+    Bracket,
+}
+impl Delimiter {
+    fn open(kind: &TokenKind) -> Option<Self> {
+        if kind.is::<LeftParen>() {
+            Some(Self::Paren)
+        } else if kind.is::<LeftBrace>() {
+            Some(Self::Brace)
+        } else if kind.is::<LeftBracket>() {
+            Some(Self::Bracket)
+        } else {
+            None
+        }
+    }
+    fn close(kind: &TokenKind) -> Option<Self> {
+        if kind.is::<RightParen>() {
+            Some(Self::Paren)
+        } else if kind.is::<RightBrace>() {
+            Some(Self::Brace)
+        } else if kind.is::<RightBracket>() {
+            Some(Self::Bracket)
+        } else {
+            None
+        }
+    }
+    fn expected_close(self) -> &'static str {
+        match self {
+            Self::Paren => "`)`",
+            Self::Brace => "`}`",
+            Self::Bracket => "`]`",
+        }
+    }
+}
+
+/// Tokens, with delimited groups nested.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TokenTree {
+    /// A token that isn't a delimiter. `kind` indexes into the registry.
+    Leaf { kind: u16, span: Span },
+    Group {
+        delimiter: Delimiter,
+        open: Span,
+        close: Span,
+        children: Vec<TokenTree>,
+    },
+}
+impl TokenTree {
+    pub fn span(&self) -> Span {
+        match self {
+            Self::Leaf { span, .. } => *span,
+            Self::Group { open, close, .. } => open.merge(*close),
+        }
+    }
+}
+
+impl<'a, const LOOKAHEAD: usize> Lexer<'a, LOOKAHEAD> {
+    /// Lexes the rest of the input with `registry` like
+    /// [`tokenize_all`](Lexer::tokenize_all), then nests it into token trees.
+    ///
+    /// Delimiters are only recognized if their token types are in `registry`.
+    ///
+    /// ## Panics
+    /// Panics if `registry` has more than `u16::MAX` kinds.
+    pub fn tokenize_trees<'r>(self, registry: &'r [TokenKind]) -> Result<'a, Vec<TokenTree>>
+    where
+        'a: 'r,
+    {
+        let input = self.input;
+        let buffer = self.tokenize_all(registry)?;
+        let mut stack: Vec<(Delimiter, Span, Vec<TokenTree>)> = Vec::new();
+        let mut trees = Vec::new();
+        for (&kind, span) in buffer.kinds().iter().zip(buffer.spans()) {
+            let token_kind = &registry[kind as usize];
+            if let Some(delimiter) = Delimiter::open(token_kind) {
+                stack.push((delimiter, *span, core::mem::take(&mut trees)));
+            } else if let Some(delimiter) = Delimiter::close(token_kind) {
+                let Some((open_delimiter, open, parent)) = stack.pop() else {
+                    return Err(Error::UnmatchedDelimiter {
+                        span: *span,
+                        found: &input[*span],
+                    });
+                };
+                if open_delimiter != delimiter {
+                    return Err(Error::UnexpectedToken {
+                        unexpected: &input[*span],
+                        expected: open_delimiter.expected_close(),
+                    });
+                }
+                let children = core::mem::replace(&mut trees, parent);
+                trees.push(TokenTree::Group {
+                    delimiter,
+                    open,
+                    close: *span,
+                    children,
+                });
+            } else {
+                trees.push(TokenTree::Leaf { kind, span: *span });
+            }
+        }
+        if let Some((delimiter, open, _)) = stack.pop() {
+            return Err(Error::Unterminated {
+                span: open,
+                expected: delimiter.expected_close(),
+            });
+        }
+        Ok(trees)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenize_trees() {
+        let registry = [
+            TokenKind::of::<Ident>(),
+            TokenKind::of::<Comma>(),
+            TokenKind::of::<LeftParen>(),
+            TokenKind::of::<RightParen>(),
+            TokenKind::of::<LeftBracket>(),
+            TokenKind::of::<RightBracket>(),
+        ];
+        let trees = Lexer::<1>::new("f(a, [b]) c")
+            .tokenize_trees(&registry)
+            .unwrap();
+        assert_eq!(
+            trees,
+            [
+                TokenTree::Leaf {
+                    kind: 0,
+                    span: Span::new(0, 1)
+                },
+                TokenTree::Group {
+                    delimiter: Delimiter::Paren,
+                    open: Span::new(1, 2),
+                    close: Span::new(8, 9),
+                    children: alloc::vec![
+                        TokenTree::Leaf {
+                            kind: 0,
+                            span: Span::new(2, 3)
+                        },
+                        TokenTree::Leaf {
+                            kind: 1,
+                            span: Span::new(3, 4)
+                        },
+                        TokenTree::Group {
+                            delimiter: Delimiter::Bracket,
+                            open: Span::new(5, 6),
+                            close: Span::new(7, 8),
+                            children: alloc::vec![TokenTree::Leaf {
+                                kind: 0,
+                                span: Span::new(6, 7)
+                            }],
+                        },
+                    ],
+                },
+                TokenTree::Leaf {
+                    kind: 0,
+                    span: Span::new(10, 11)
+                },
+            ]
+        );
+        assert_eq!(trees[1].span(), 1..9);
+
+        assert!(matches!(
+            Lexer::<1>::new("(a]").tokenize_trees(&registry),
+            Err(Error::UnexpectedToken {
+                unexpected: "]",
+                expected: "`)`"
+            })
+        ));
+        assert!(matches!(
+            Lexer::<1>::new("f(a").tokenize_trees(&registry),
+            Err(Error::Unterminated { span, expected: "`)`" }) if span == (1..2)
+        ));
+        assert!(matches!(
+            Lexer::<1>::new("a)").tokenize_trees(&registry),
+            Err(Error::UnmatchedDelimiter { span, found: ")" }) if span == (1..2)
+        ));
+    }
+}
@@ -1,4 +1,12 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 pub mod error;
+#[cfg(feature = "alloc")]
+pub mod fuzz;
 pub mod lexer;
+pub mod span;
@@ -0,0 +1,93 @@
+use core::fmt;
+use core::ops::{Index, Range};
+
+/// A byte range in the input.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+    /// The smallest span covering both `self` and `other`.
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            start: self.start.min(other.start),
+            end: self.end.max(other.end),
+        }
+    }
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+    pub fn contains_span(&self, other: Self) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+    /// Whether the spans share at least one byte.
+    pub fn intersects(&self, other: Self) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+    /// An empty span at the start of `self`.
+    pub fn shrink_to_start(self) -> Self {
+        Self::new(self.start, self.start)
+    }
+    /// An empty span at the end of `self`.
+    pub fn shrink_to_end(self) -> Self {
+        Self::new(self.end, self.end)
+    }
+}
+impl From<Range<usize>> for Span {
+    fn from(range: Range<usize>) -> Self {
+        Self::new(range.start, range.end)
+    }
+}
+impl From<Span> for Range<usize> {
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+impl PartialEq<Range<usize>> for Span {
+    fn eq(&self, other: &Range<usize>) -> bool {
+        self.start == other.start && self.end == other.end
+    }
+}
+impl fmt::Display for Span {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}..{}", self.start, self.end)
+    }
+}
+impl Index<Span> for str {
+    type Output = str;
+    fn index(&self, span: Span) -> &str {
+        &self[Range::from(span)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn span() {
+        let a = Span::new(2, 5);
+        let b = Span::from(4..8);
+        assert_eq!(a.merge(b), 2..8);
+        assert!(a.intersects(b));
+        assert!(!a.intersects(Span::new(5, 6)));
+        assert!(a.contains(4));
+        assert!(!a.contains(5));
+        assert!(a.merge(b).contains_span(b));
+        assert!(!a.contains_span(b));
+        assert_eq!(a.shrink_to_start(), 2..2);
+        assert_eq!(a.shrink_to_end(), 5..5);
+        assert_eq!(Range::from(a), 2..5);
+        assert_eq!(&"abcdefg"[a], "cde");
+    }
+}